@@ -0,0 +1,151 @@
+//! In-memory keystore backend
+
+use crate::{KeyHandle, KeyInfo, KeyName, KeyRing, LoadPkcs8, Result};
+use pkcs8::der::Decode;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Mutex, MutexGuard},
+};
+use zeroize::Zeroizing;
+
+/// In-memory keystore backend.
+///
+/// Keeps PKCS#8 documents in a `HashMap` rather than on disk, making it a
+/// drop-in backend for tests and ephemeral services that must never touch
+/// the filesystem.
+#[derive(Default)]
+pub struct MemKeyStore {
+    keys: Mutex<HashMap<KeyName, Zeroizing<Vec<u8>>>>,
+}
+
+impl MemKeyStore {
+    /// Create a new, empty in-memory keystore.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get information about a key with the given name.
+    pub fn info(&self, name: &KeyName) -> Result<KeyInfo> {
+        let der = self.key_der(name, &self.keys())?;
+
+        Ok(KeyInfo {
+            name: name.clone(),
+            algorithm: pkcs8::PrivateKeyInfo::from_der(&der)?
+                .algorithm
+                .try_into()
+                .ok(),
+            encrypted: false,
+        })
+    }
+
+    /// Enumerate all keys held by this keystore.
+    pub fn list(&self) -> Result<Vec<KeyInfo>> {
+        let names: Vec<KeyName> = self.keys().keys().cloned().collect();
+        names.iter().map(|name| self.info(name)).collect()
+    }
+
+    /// Load a PKCS#8 key from the keystore.
+    pub fn load(&self, name: &KeyName) -> Result<pkcs8::SecretDocument> {
+        let der = self.key_der(name, &self.keys())?;
+        Ok(pkcs8::SecretDocument::from_der(&der)?)
+    }
+
+    /// Import a PKCS#8 key into the keystore.
+    pub fn store(&self, name: &KeyName, der: &pkcs8::SecretDocument) -> Result<()> {
+        self.keys()
+            .insert(name.clone(), Zeroizing::new(der.as_bytes().to_vec()));
+
+        Ok(())
+    }
+
+    /// Delete a PKCS#8 key from the keystore.
+    pub fn delete(&self, name: &KeyName) -> Result<()> {
+        self.keys()
+            .remove(name)
+            .map(drop)
+            .ok_or_else(Self::not_found)
+    }
+
+    /// Import a key with a given name into the provided keyring.
+    pub fn import(&self, name: &KeyName, key_ring: &mut KeyRing) -> Result<KeyHandle> {
+        key_ring.load_pkcs8(self.load(name)?.decode_msg()?)
+    }
+
+    /// Acquire the lock on the underlying key map.
+    fn keys(&self) -> MutexGuard<'_, HashMap<KeyName, Zeroizing<Vec<u8>>>> {
+        self.keys.lock().expect("keystore lock poisoned")
+    }
+
+    /// Look up the DER for a stored key, erroring if it isn't present.
+    fn key_der(
+        &self,
+        name: &KeyName,
+        keys: &HashMap<KeyName, Zeroizing<Vec<u8>>>,
+    ) -> Result<Vec<u8>> {
+        keys.get(name)
+            .map(|der| der.to_vec())
+            .ok_or_else(Self::not_found)
+    }
+
+    /// Build the "no such key" error used throughout this backend.
+    fn not_found() -> crate::Error {
+        io::Error::from(io::ErrorKind::NotFound).into()
+    }
+}
+
+impl super::KeyStore for MemKeyStore {
+    fn info(&self, name: &KeyName) -> Result<KeyInfo> {
+        MemKeyStore::info(self, name)
+    }
+
+    fn list(&self) -> Result<Vec<KeyInfo>> {
+        MemKeyStore::list(self)
+    }
+
+    fn load(&self, name: &KeyName) -> Result<pkcs8::SecretDocument> {
+        MemKeyStore::load(self, name)
+    }
+
+    fn store(&self, name: &KeyName, der: &pkcs8::SecretDocument) -> Result<()> {
+        MemKeyStore::store(self, name, der)
+    }
+
+    fn delete(&self, name: &KeyName) -> Result<()> {
+        MemKeyStore::delete(self, name)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)] // TODO(tarcieri): always use imports
+mod tests {
+    use super::MemKeyStore;
+    use crate::{Algorithm, GeneratePkcs8};
+
+    #[cfg(feature = "secp256k1")]
+    use crate::ecdsa::secp256k1;
+
+    pub const EXAMPLE_KEY: &str = "example-key";
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn store_load_and_delete_key() {
+        let key_name = EXAMPLE_KEY.parse().unwrap();
+        let example_key = secp256k1::SigningKey::generate_pkcs8();
+        let keystore = MemKeyStore::new();
+
+        keystore.store(&key_name, &example_key).unwrap();
+
+        let loaded = keystore.load(&key_name).unwrap();
+        assert_eq!(example_key.as_bytes(), loaded.as_bytes());
+
+        let info = keystore.info(&key_name).unwrap();
+        assert_eq!(info.algorithm, Some(Algorithm::EcdsaSecp256k1));
+        assert!(!info.encrypted);
+
+        assert_eq!(keystore.list().unwrap().len(), 1);
+
+        keystore.delete(&key_name).unwrap();
+        assert!(keystore.load(&key_name).is_err());
+    }
+}
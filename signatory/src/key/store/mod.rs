@@ -0,0 +1,36 @@
+//! Key storage backends
+
+mod fs;
+mod mem;
+mod paperkey;
+mod web3;
+
+pub use self::{fs::FsKeyStore, mem::MemKeyStore, paperkey::PaperkeyFormat};
+
+use crate::{KeyHandle, KeyInfo, KeyName, KeyRing, LoadPkcs8, Result};
+
+/// Common operations supported by all keystore backends.
+///
+/// This allows downstream code to be generic over where keys are actually
+/// persisted, e.g. swapping [`FsKeyStore`] for [`MemKeyStore`] in tests.
+pub trait KeyStore {
+    /// Get information about a key with the given name.
+    fn info(&self, name: &KeyName) -> Result<KeyInfo>;
+
+    /// Enumerate all keys held by this keystore.
+    fn list(&self) -> Result<Vec<KeyInfo>>;
+
+    /// Load a PKCS#8 key from the keystore.
+    fn load(&self, name: &KeyName) -> Result<pkcs8::SecretDocument>;
+
+    /// Import a PKCS#8 key into the keystore.
+    fn store(&self, name: &KeyName, der: &pkcs8::SecretDocument) -> Result<()>;
+
+    /// Delete a PKCS#8 key from the keystore.
+    fn delete(&self, name: &KeyName) -> Result<()>;
+
+    /// Import a key with a given name into the provided keyring.
+    fn import(&self, name: &KeyName, key_ring: &mut KeyRing) -> Result<KeyHandle> {
+        key_ring.load_pkcs8(self.load(name)?.decode_msg()?)
+    }
+}
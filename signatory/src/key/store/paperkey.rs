@@ -0,0 +1,269 @@
+//! Paperkey export/import: rendering a stored key as a human-printable
+//! offline backup, and reconstructing it from one.
+
+use crate::{KeyName, Result};
+use qrcode::{render::svg, QrCode};
+use sha2::{Digest, Sha256};
+
+/// Output format for [`super::FsKeyStore::export_paperkey`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PaperkeyFormat {
+    /// A plain text block: an algorithm/fingerprint header followed by the
+    /// key's PEM document.
+    Text,
+
+    /// The same content as [`PaperkeyFormat::Text`], wrapped in minimal HTML
+    /// suitable for printing.
+    Html,
+
+    /// The PEM document's base64 body, chunked one line per scannable QR
+    /// code and rendered as SVG.
+    QrSvg,
+}
+
+/// Marker identifying a QR chunk comment within a [`PaperkeyFormat::QrSvg`]
+/// document, so `parse` can find and reorder them.
+const QR_CHUNK_MARKER: &str = "signatory-paperkey chunk";
+
+/// Upper bound on the chunk count a [`PaperkeyFormat::QrSvg`] backup can
+/// declare, so a crafted/corrupted `total` marker can't force an unbounded
+/// allocation in `reassemble_qr_chunks`. A handful of thousand chunks is
+/// already far more than any real paper backup would need.
+const MAX_QR_CHUNKS: usize = 4096;
+
+/// Render a key's PEM document (as stored on disk, plaintext or encrypted)
+/// into a human-printable paperkey backup.
+pub(crate) fn render(name: &KeyName, pem: &str, format: PaperkeyFormat) -> Result<String> {
+    let (label, body) = split_pem(pem)?;
+    let fingerprint = hex::encode(Sha256::digest(pem.as_bytes()));
+
+    // The algorithm can only be read from a plaintext `PrivateKeyInfo`;
+    // encrypted keys are labelled `unknown`.
+    let algorithm: Option<crate::Algorithm> = pkcs8::SecretDocument::from_pem(pem)
+        .ok()
+        .and_then(|(_, doc)| {
+            let info = doc.decode_msg::<pkcs8::PrivateKeyInfo<'_>>().ok()?;
+            info.algorithm.try_into().ok()
+        });
+    let algorithm = algorithm.map_or_else(|| "unknown".to_owned(), |alg| format!("{alg:?}"));
+
+    let header = format!(
+        "Signatory paperkey backup\nName: {name}\nFormat: {label}\nAlgorithm: {algorithm}\nSHA-256: {fingerprint}"
+    );
+
+    match format {
+        PaperkeyFormat::Text => Ok(format!("{header}\n\n{pem}")),
+        PaperkeyFormat::Html => Ok(format!(
+            "<pre>\n{}\n\n{}\n</pre>\n",
+            html_escape(&header),
+            html_escape(pem)
+        )),
+        PaperkeyFormat::QrSvg => render_qr_svg(&header, &body),
+    }
+}
+
+/// Reconstruct a key's PEM document from a previously rendered paperkey
+/// backup, in any [`PaperkeyFormat`].
+pub(crate) fn parse(data: &str) -> Result<String> {
+    if let Some(pem) = extract_pem(data) {
+        return Ok(pem);
+    }
+
+    reassemble_qr_chunks(data)
+}
+
+/// Split a PEM document into its encapsulation label and base64 body lines.
+fn split_pem(pem: &str) -> Result<(String, Vec<&str>)> {
+    let mut lines = pem.lines();
+
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or(pkcs8::Error::KeyMalformed)?
+        .to_owned();
+
+    let body = lines
+        .take_while(|line| !line.starts_with("-----END"))
+        .collect();
+
+    Ok((label, body))
+}
+
+/// Find and extract a `-----BEGIN ... -----`/`-----END ... -----`
+/// encapsulated PEM document embedded anywhere within `data`.
+fn extract_pem(data: &str) -> Option<String> {
+    let start = data.find("-----BEGIN ")?;
+    let end_start = start + data[start..].find("-----END ")?;
+    let end = end_start
+        + data[end_start..]
+            .find('\n')
+            .unwrap_or(data.len() - end_start);
+
+    Some(format!("{}\n", data[start..end].trim_end()))
+}
+
+/// Render a paperkey header and PEM body as a sequence of QR codes, one per
+/// base64 line, concatenated as SVG.
+fn render_qr_svg(header: &str, body: &[&str]) -> Result<String> {
+    let total = body.len();
+    let mut svg_doc = format!("<!--\n{header}\n-->\n");
+
+    for (i, chunk) in body.iter().enumerate() {
+        let code = QrCode::new(chunk.as_bytes()).map_err(|_| pkcs8::Error::KeyMalformed)?;
+        let image = code.render::<svg::Color>().build();
+
+        svg_doc.push_str(&format!(
+            "<!-- {QR_CHUNK_MARKER} {}/{total} {chunk} -->\n{image}\n",
+            i + 1,
+        ));
+    }
+
+    Ok(svg_doc)
+}
+
+/// Recover the original base64 body from the numbered QR chunk comments
+/// left behind by [`render_qr_svg`], then re-wrap it as a PEM document.
+///
+/// Every chunk declares the total chunk count alongside its own index; all
+/// of `1..=total` must be present, with no duplicates or gaps, or this is
+/// rejected rather than silently reassembling a truncated body — a missed
+/// chunk when scanning/retyping a paper backup is exactly the failure mode
+/// this format exists to survive.
+fn reassemble_qr_chunks(data: &str) -> Result<String> {
+    let chunks: Vec<(usize, usize, &str)> = data
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("<!--")?.trim();
+            let rest = rest.strip_prefix(QR_CHUNK_MARKER)?.trim();
+            let (position, rest) = rest.split_once(' ')?;
+            let (index, total) = position.split_once('/')?;
+            let index: usize = index.parse().ok()?;
+            let total: usize = total.parse().ok()?;
+            let chunk = rest.trim_end_matches("-->").trim();
+            Some((index, total, chunk))
+        })
+        .collect();
+
+    if chunks.is_empty() {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let total = chunks[0].1;
+    if !(1..=MAX_QR_CHUNKS).contains(&total) || chunks.iter().any(|(_, t, _)| *t != total) {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let mut by_index = vec![None; total];
+    for (index, _, chunk) in &chunks {
+        // `index` is 1-based; reject out-of-range or duplicate indices.
+        match index.checked_sub(1).and_then(|i| by_index.get_mut(i)) {
+            Some(slot @ None) => *slot = Some(*chunk),
+            _ => return Err(pkcs8::Error::KeyMalformed.into()),
+        }
+    }
+
+    let body = by_index
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or(pkcs8::Error::KeyMalformed)?
+        .join("\n");
+
+    let label = data
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Format: "))
+        .ok_or(pkcs8::Error::KeyMalformed)?;
+
+    let pem = format!("-----BEGIN {label}-----\n{body}\n-----END {label}-----\n");
+
+    // Make sure the reassembled body decodes as a well-formed PKCS#8
+    // document before handing it back to be written to disk.
+    pkcs8::SecretDocument::from_pem(&pem)?;
+
+    Ok(pem)
+}
+
+/// Minimal HTML-escaping for paperkey text embedded in a `<pre>` block.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, render, PaperkeyFormat, MAX_QR_CHUNKS};
+
+    #[cfg(feature = "secp256k1")]
+    fn example_pem() -> String {
+        use crate::{ecdsa::secp256k1, GeneratePkcs8};
+        use pkcs8::der::pem::PemLabel;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("example.pem");
+
+        secp256k1::SigningKey::generate_pkcs8()
+            .write_pem_file(&path, pkcs8::PrivateKeyInfo::PEM_LABEL, Default::default())
+            .unwrap();
+
+        std::fs::read_to_string(&path).unwrap()
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn qr_round_trip() {
+        let name = "example-key".parse().unwrap();
+        let pem = example_pem();
+
+        let rendered = render(&name, &pem, PaperkeyFormat::QrSvg).unwrap();
+        let recovered = parse(&rendered).unwrap();
+        assert_eq!(pem, recovered);
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn qr_rejects_missing_chunk() {
+        let name = "example-key".parse().unwrap();
+        let pem = example_pem();
+        let rendered = render(&name, &pem, PaperkeyFormat::QrSvg).unwrap();
+
+        // Drop the first chunk's marker comment entirely, simulating a chunk
+        // that was missed when scanning/retyping the backup.
+        let first_marker = rendered
+            .lines()
+            .find(|line| line.contains(super::QR_CHUNK_MARKER))
+            .unwrap();
+        let without_first_chunk = rendered.replacen(first_marker, "", 1);
+
+        assert!(parse(&without_first_chunk).is_err());
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn qr_rejects_duplicate_chunk_index() {
+        let name = "example-key".parse().unwrap();
+        let pem = example_pem();
+        let rendered = render(&name, &pem, PaperkeyFormat::QrSvg).unwrap();
+
+        let first_marker = rendered
+            .lines()
+            .find(|line| line.contains(super::QR_CHUNK_MARKER))
+            .unwrap()
+            .to_owned();
+        let duplicated = format!("{rendered}\n{first_marker}\n");
+
+        assert!(parse(&duplicated).is_err());
+    }
+
+    /// A crafted `total` marker must not force an unbounded allocation.
+    #[test]
+    fn qr_rejects_oversized_total() {
+        let data = format!(
+            "Format: PRIVATE KEY\n<!-- {} 1/{} abcd -->\n",
+            super::QR_CHUNK_MARKER,
+            MAX_QR_CHUNKS + 1,
+        );
+
+        assert!(super::reassemble_qr_chunks(&data).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! Import/export of Web3 Secret Storage ("geth v3") keystore JSON files.
+//!
+//! See the [Web3 Secret Storage Definition] for the format this module
+//! speaks.
+//!
+//! [Web3 Secret Storage Definition]: https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition
+
+use crate::Result;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use pbkdf2::pbkdf2_hmac;
+use rand_core::{OsRng, RngCore};
+use scrypt::scrypt;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// AES-128-CTR as used by the `aes-128-ctr` cipher in Web3 Secret Storage.
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Length in bytes of the secp256k1 private key scalar wrapped by a Web3
+/// keystore file.
+const SECRET_KEY_LEN: usize = 32;
+
+/// Top-level Web3 Secret Storage keystore document.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Web3Keystore {
+    crypto: CryptoParams,
+    version: u8,
+}
+
+/// The `crypto` section of a [`Web3Keystore`].
+#[derive(Debug, Deserialize, Serialize)]
+struct CryptoParams {
+    ciphertext: String,
+    cipherparams: CipherParams,
+    cipher: String,
+    kdf: String,
+    kdfparams: KdfParams,
+    mac: String,
+}
+
+/// The `cipherparams` section of a [`Web3Keystore`].
+#[derive(Debug, Deserialize, Serialize)]
+struct CipherParams {
+    iv: String,
+}
+
+/// The `kdfparams` section of a [`Web3Keystore`], covering both `scrypt`
+/// and `pbkdf2` key derivation functions.
+#[derive(Debug, Deserialize, Serialize)]
+struct KdfParams {
+    dklen: usize,
+    salt: String,
+
+    // scrypt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p: Option<u32>,
+
+    // pbkdf2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prf: Option<String>,
+}
+
+/// Parse a Web3 Secret Storage JSON document and recover the 32-byte
+/// secp256k1 secret key it wraps.
+pub(crate) fn decrypt(json: &str, passphrase: &Zeroizing<String>) -> Result<Zeroizing<Vec<u8>>> {
+    let keystore: Web3Keystore =
+        serde_json::from_str(json).map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+
+    if keystore.version != 3 {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let crypto = &keystore.crypto;
+
+    if crypto.cipher != "aes-128-ctr" {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let dk = derive_key(&crypto.kdf, &crypto.kdfparams, passphrase)?;
+    let ciphertext =
+        hex::decode(&crypto.ciphertext).map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+    let iv = hex::decode(&crypto.cipherparams.iv).map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+    let mac = hex::decode(&crypto.mac).map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+
+    if iv.len() != 16 {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&dk[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let expected_mac = Keccak256::digest(&mac_input);
+
+    if expected_mac.ct_eq(mac.as_slice()).unwrap_u8() != 1 {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let mut secret_key = Zeroizing::new(ciphertext);
+    let mut cipher = Aes128Ctr::new(dk[..16].into(), iv.as_slice().into());
+    cipher.apply_keystream(&mut secret_key);
+
+    if secret_key.len() != SECRET_KEY_LEN {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    Ok(secret_key)
+}
+
+/// Encrypt a 32-byte secp256k1 secret key into a Web3 Secret Storage JSON
+/// document, using scrypt for key derivation.
+pub(crate) fn encrypt(secret_key: &[u8], passphrase: &Zeroizing<String>) -> Result<String> {
+    let mut salt = [0u8; 32];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut iv = [0u8; 16];
+    OsRng.fill_bytes(&mut iv);
+
+    let kdfparams = KdfParams {
+        dklen: 32,
+        salt: hex::encode(salt),
+        n: Some(1 << 18),
+        r: Some(8),
+        p: Some(1),
+        c: None,
+        prf: None,
+    };
+
+    let dk = derive_key("scrypt", &kdfparams, passphrase)?;
+
+    let mut ciphertext = secret_key.to_vec();
+    let mut cipher = Aes128Ctr::new(dk[..16].into(), iv.as_slice().into());
+    cipher.apply_keystream(&mut ciphertext);
+
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&dk[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let mac = Keccak256::digest(&mac_input);
+
+    let keystore = Web3Keystore {
+        crypto: CryptoParams {
+            ciphertext: hex::encode(&ciphertext),
+            cipherparams: CipherParams {
+                iv: hex::encode(iv),
+            },
+            cipher: "aes-128-ctr".to_owned(),
+            kdf: "scrypt".to_owned(),
+            kdfparams,
+            mac: hex::encode(mac),
+        },
+        version: 3,
+    };
+
+    serde_json::to_string(&keystore).map_err(|_| pkcs8::Error::KeyMalformed.into())
+}
+
+/// Minimum accepted `dklen`: the MAC and cipher key are sliced out of the
+/// derived key at fixed offsets (`dk[16..32]` and `dk[..16]`), so anything
+/// shorter is malformed input.
+const MIN_DKLEN: usize = 32;
+
+/// Upper bound on `dklen` so a hostile keystore file can't force an
+/// unbounded allocation before the KDF parameters are otherwise validated.
+const MAX_DKLEN: usize = 256;
+
+/// Derive a `dklen`-byte key from a passphrase using the KDF named by a
+/// Web3 keystore's `crypto.kdf` field.
+fn derive_key(
+    kdf: &str,
+    params: &KdfParams,
+    passphrase: &Zeroizing<String>,
+) -> Result<Zeroizing<Vec<u8>>> {
+    if !(MIN_DKLEN..=MAX_DKLEN).contains(&params.dklen) {
+        return Err(pkcs8::Error::KeyMalformed.into());
+    }
+
+    let salt = hex::decode(&params.salt).map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+    let mut dk = Zeroizing::new(vec![0u8; params.dklen]);
+
+    match kdf {
+        "scrypt" => {
+            let n = params.n.ok_or(pkcs8::Error::KeyMalformed.into())?;
+            let r = params.r.ok_or(pkcs8::Error::KeyMalformed.into())?;
+            let p = params.p.ok_or(pkcs8::Error::KeyMalformed.into())?;
+            if n == 0 || !n.is_power_of_two() {
+                return Err(pkcs8::Error::KeyMalformed.into());
+            }
+            let log_n = n.trailing_zeros() as u8;
+            let scrypt_params = scrypt::Params::new(log_n, r, p, params.dklen)
+                .map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+            scrypt(passphrase.as_bytes(), &salt, &scrypt_params, &mut dk)
+                .map_err(|_| pkcs8::Error::KeyMalformed.into())?;
+        }
+        "pbkdf2" => {
+            if params.prf.as_deref() != Some("hmac-sha256") {
+                return Err(pkcs8::Error::KeyMalformed.into());
+            }
+            let c = params.c.ok_or(pkcs8::Error::KeyMalformed.into())?;
+            pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), &salt, c, &mut dk);
+        }
+        _ => return Err(pkcs8::Error::KeyMalformed.into()),
+    }
+
+    Ok(dk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, KdfParams};
+    use zeroize::Zeroizing;
+
+    /// A `dklen` shorter than the 32 bytes `decrypt`/`encrypt` slice out of
+    /// the derived key must be rejected, not panic on an out-of-bounds
+    /// slice index.
+    #[test]
+    fn rejects_undersized_dklen() {
+        let passphrase = Zeroizing::new("hunter2".to_owned());
+        let keystore = serde_json::json!({
+            "version": 3,
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "ciphertext": "00".repeat(32),
+                "cipherparams": { "iv": "00".repeat(16) },
+                "mac": "00".repeat(32),
+                "kdf": "scrypt",
+                "kdfparams": {
+                    "dklen": 16,
+                    "salt": "00".repeat(32),
+                    "n": 2,
+                    "r": 1,
+                    "p": 1,
+                },
+            },
+        })
+        .to_string();
+
+        assert!(decrypt(&keystore, &passphrase).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_dklen() {
+        let params = KdfParams {
+            dklen: 1 << 20,
+            salt: "00".repeat(32),
+            n: Some(2),
+            r: Some(1),
+            p: Some(1),
+            c: None,
+            prf: None,
+        };
+        let passphrase = Zeroizing::new("hunter2".to_owned());
+
+        assert!(super::derive_key("scrypt", &params, &passphrase).is_err());
+    }
+
+    /// An `n` of zero is not a valid scrypt cost and must be rejected.
+    #[test]
+    fn rejects_zero_scrypt_n() {
+        let params = KdfParams {
+            dklen: 32,
+            salt: "00".repeat(32),
+            n: Some(0),
+            r: Some(1),
+            p: Some(1),
+            c: None,
+            prf: None,
+        };
+        let passphrase = Zeroizing::new("hunter2".to_owned());
+
+        assert!(super::derive_key("scrypt", &params, &passphrase).is_err());
+    }
+
+    /// A non-power-of-two `n` must be rejected rather than silently replaced
+    /// by the power of two given by its trailing zero bits.
+    #[test]
+    fn rejects_non_power_of_two_scrypt_n() {
+        let params = KdfParams {
+            dklen: 32,
+            salt: "00".repeat(32),
+            n: Some(3),
+            r: Some(1),
+            p: Some(1),
+            c: None,
+            prf: None,
+        };
+        let passphrase = Zeroizing::new("hunter2".to_owned());
+
+        assert!(super::derive_key("scrypt", &params, &passphrase).is_err());
+    }
+
+    /// A `cipherparams.iv` that isn't exactly 16 bytes must be rejected, not
+    /// panic constructing the AES-CTR cipher.
+    #[test]
+    fn rejects_wrong_length_iv() {
+        let passphrase = Zeroizing::new("hunter2".to_owned());
+        let keystore = serde_json::json!({
+            "version": 3,
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "ciphertext": "00".repeat(32),
+                "cipherparams": { "iv": "00".repeat(8) },
+                "mac": "00".repeat(32),
+                "kdf": "scrypt",
+                "kdfparams": {
+                    "dklen": 32,
+                    "salt": "00".repeat(32),
+                    "n": 2,
+                    "r": 1,
+                    "p": 1,
+                },
+            },
+        })
+        .to_string();
+
+        assert!(decrypt(&keystore, &passphrase).is_err());
+    }
+}